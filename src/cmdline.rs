@@ -2,8 +2,13 @@
 use crate::Result;
 use core::ffi::CStr;
 use nix::mount::MsFlags;
+use std::collections::HashMap;
 use std::ffi::CString;
 use std::fs::read_to_string;
+use std::io::{BufRead, Write};
+use std::os::unix::fs::MetadataExt;
+use std::path::Path;
+use std::time::Duration;
 
 pub struct CmdlineOptions {
     pub root: Option<String>,
@@ -12,9 +17,54 @@ pub struct CmdlineOptions {
     pub rootfsflags: MsFlags,
     pub nfsroot: Option<String>,
     pub init: CString,
+    pub ip: Option<IpConfig>,
+    pub rootwait: bool,
+    pub rootdelay: Option<Duration>,
+    pub luks: Option<LuksConfig>,
+    pub luks_key: Option<String>,
+    pub cryptkey: Option<String>,
+    pub emergency: bool,
+    pub rescue_shell: CString,
+    pub panic_timeout: Option<Duration>,
+    // Split from root= for a multi-device bcachefs or btrfs root, e.g.
+    // /dev/sda:/dev/sdb.
+    pub root_devices: Vec<String>,
+    // Note: cryptkey is intentionally excluded; see parse_option.
+    vars: HashMap<String, Option<String>>,
+}
+
+pub struct LuksConfig {
+    pub uuid: String,
+    pub name: String,
+}
+
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct IpConfig {
+    pub client_ip: Option<String>,
+    pub server_ip: Option<String>,
+    pub gw_ip: Option<String>,
+    pub netmask: Option<String>,
+    pub hostname: Option<String>,
+    pub device: Option<String>,
+    pub autoconf: Autoconf,
+    pub dns0_ip: Option<String>,
+    pub dns1_ip: Option<String>,
+    pub ntp_ip: Option<String>,
+}
+
+#[derive(Debug, Default, PartialEq, Eq)]
+pub enum Autoconf {
+    #[default]
+    Off,
+    Dhcp,
+    Bootp,
+    Rarp,
+    Any,
 }
 
 const SBIN_INIT: &CStr = c"/sbin/init";
+const BIN_SH: &CStr = c"/bin/sh";
+const PROC_CMDLINE: &str = "/proc/cmdline";
 
 impl Default for CmdlineOptions {
     fn default() -> CmdlineOptions {
@@ -25,10 +75,51 @@ impl Default for CmdlineOptions {
             rootfsflags: MsFlags::MS_RDONLY,
             nfsroot: None,
             init: CString::from(SBIN_INIT),
+            ip: None,
+            rootwait: false,
+            rootdelay: None,
+            luks: None,
+            luks_key: None,
+            cryptkey: None,
+            emergency: false,
+            rescue_shell: CString::from(BIN_SH),
+            panic_timeout: None,
+            root_devices: Vec::new(),
+            vars: HashMap::new(),
         }
     }
 }
 
+impl CmdlineOptions {
+    pub fn load() -> Result<CmdlineOptions> {
+        if !Path::new(PROC_CMDLINE).exists() {
+            nix::mount::mount(
+                Some("proc"),
+                "/proc",
+                Some("proc"),
+                MsFlags::empty(),
+                None::<&str>,
+            )
+            .map_err(|e| format!("Failed to mount /proc: {e}"))?;
+        }
+        let cmdline = read_to_string(PROC_CMDLINE)
+            .map_err(|e| format!("Failed to read {PROC_CMDLINE}: {e}"))?;
+        let mut options = CmdlineOptions::default();
+        parse_cmdline(cmdline, &mut options)?;
+        Ok(options)
+    }
+
+    pub fn has_var(&self, name: &str) -> bool {
+        self.vars.contains_key(name)
+    }
+
+    // A bare flag has no value, so lookup() returns None both when name was
+    // absent and when it was given bare; use has_var() to tell those apart.
+    pub fn lookup(&self, name: &str) -> Option<String> {
+        self.vars.get(name).cloned().flatten()
+    }
+}
+
 fn ensure_value(key: String, value: Option<String>) -> Result<Option<String>> {
     if value.is_none() {
         Err(format!("Cmdline option '{key}' must have an argument!").into())
@@ -38,6 +129,11 @@ fn ensure_value(key: String, value: Option<String>) -> Result<Option<String>> {
 }
 
 fn parse_option(key: String, value: Option<String>, options: &mut CmdlineOptions) -> Result<()> {
+    // cryptkey can carry a literal passphrase baked into the cmdline; don't
+    // let it linger forever in the generic lookup map.
+    if key != "cryptkey" {
+        options.vars.insert(key.clone(), value.clone());
+    }
     match key.as_str() {
         "root" => options.root = ensure_value(key, value)?,
         "rootfstype" => options.rootfstype = ensure_value(key, value)?,
@@ -46,11 +142,83 @@ fn parse_option(key: String, value: Option<String>, options: &mut CmdlineOptions
         "rw" => options.rootfsflags.remove(MsFlags::MS_RDONLY),
         "nfsroot" => options.nfsroot = ensure_value(key, value)?,
         "init" => options.init = CString::new(ensure_value(key, value)?.unwrap()).unwrap(),
+        "ip" => options.ip = Some(parse_ip(&ensure_value(key, value)?.unwrap())),
+        "rootwait" => options.rootwait = true,
+        "rootdelay" => {
+            let seconds: u64 = ensure_value(key, value)?
+                .unwrap()
+                .parse()
+                .map_err(|e| format!("Invalid rootdelay value: {e}"))?;
+            options.rootdelay = Some(Duration::from_secs(seconds));
+        }
+        "rd.luks.name" => {
+            let value = ensure_value(key, value)?.unwrap();
+            let (uuid, name) = value
+                .split_once('=')
+                .ok_or("rd.luks.name must be of the form <uuid>=<name>")?;
+            options.luks = Some(LuksConfig {
+                uuid: uuid.to_string(),
+                name: name.to_string(),
+            });
+        }
+        "rd.luks.key" => options.luks_key = ensure_value(key, value)?,
+        "cryptkey" => options.cryptkey = ensure_value(key, value)?,
+        "emergency" | "rd.shell" => options.emergency = true,
+        "rescue_shell" => {
+            options.rescue_shell = CString::new(ensure_value(key, value)?.unwrap()).unwrap()
+        }
+        "panic" => {
+            let seconds: u64 = ensure_value(key, value)?
+                .unwrap()
+                .parse()
+                .map_err(|e| format!("Invalid panic value: {e}"))?;
+            options.panic_timeout = Some(Duration::from_secs(seconds));
+        }
         _ => (),
     }
     Ok(())
 }
 
+fn parse_ip(value: &str) -> IpConfig {
+    match value {
+        "dhcp" => {
+            return IpConfig {
+                autoconf: Autoconf::Dhcp,
+                ..Default::default()
+            };
+        }
+        "off" | "none" => return IpConfig::default(),
+        _ => (),
+    }
+
+    let field = |s: &str| -> Option<String> {
+        if s.is_empty() {
+            None
+        } else {
+            Some(s.to_string())
+        }
+    };
+    let mut fields = value.split(':');
+    IpConfig {
+        client_ip: fields.next().and_then(field),
+        server_ip: fields.next().and_then(field),
+        gw_ip: fields.next().and_then(field),
+        netmask: fields.next().and_then(field),
+        hostname: fields.next().and_then(field),
+        device: fields.next().and_then(field),
+        autoconf: match fields.next() {
+            Some("dhcp") => Autoconf::Dhcp,
+            Some("bootp") => Autoconf::Bootp,
+            Some("rarp") => Autoconf::Rarp,
+            Some("any") => Autoconf::Any,
+            _ => Autoconf::Off,
+        },
+        dns0_ip: fields.next().and_then(field),
+        dns1_ip: fields.next().and_then(field),
+        ntp_ip: fields.next().and_then(field),
+    }
+}
+
 fn parse_nfsroot(options: &mut CmdlineOptions) -> Result<()> {
     if options.nfsroot.is_none() {
         return Err("Missing nfsroot command-line option!".into());
@@ -67,19 +235,34 @@ fn parse_nfsroot(options: &mut CmdlineOptions) -> Result<()> {
     };
     rootflags.push_str(",addr=");
     if !nfsroot.contains(':') {
-        let pnp = read_to_string("/proc/net/pnp")
-            .map_err(|e| format!("Failed to read /proc/net/pnp: {e}"))?;
-        for line in pnp.lines() {
-            match line.split_once(' ') {
-                None => continue,
-                Some((key, value)) => {
-                    if key == "bootserver" {
-                        nfsroot = value.to_owned() + ":" + &nfsroot;
-                        rootflags.push_str(value);
-                        break;
+        let server_ip = options.ip.as_ref().and_then(|ip| ip.server_ip.as_deref());
+        if let Some(server_ip) = server_ip {
+            nfsroot = server_ip.to_owned() + ":" + &nfsroot;
+            rootflags.push_str(server_ip);
+        } else if options
+            .ip
+            .as_ref()
+            .is_none_or(|ip| ip.autoconf != Autoconf::Off)
+        {
+            let pnp = read_to_string("/proc/net/pnp")
+                .map_err(|e| format!("Failed to read /proc/net/pnp: {e}"))?;
+            for line in pnp.lines() {
+                match line.split_once(' ') {
+                    None => continue,
+                    Some((key, value)) => {
+                        if key == "bootserver" {
+                            nfsroot = value.to_owned() + ":" + &nfsroot;
+                            rootflags.push_str(value);
+                            break;
+                        }
                     }
                 }
             }
+        } else {
+            return Err(
+                "Missing NFS boot server; specify nfsroot=<server>:<path> or ip=...:<server-ip>:..."
+                    .into(),
+            );
         }
     } else {
         let (bootserver, _) = nfsroot.split_once(':').unwrap();
@@ -134,9 +317,228 @@ pub fn parse_cmdline(cmdline: String, options: &mut CmdlineOptions) -> Result<()
     if options.root.as_deref() == Some("/dev/nfs") || options.rootfstype.as_deref() == Some("nfs") {
         parse_nfsroot(options)?;
     }
+    match options.rootfstype.as_deref() {
+        // bcachefs takes every member device as a colon-joined mount source.
+        Some("bcachefs") => {
+            if let Some(root) = &options.root {
+                let devices: Vec<String> = root.split([',', ':']).map(str::to_string).collect();
+                options.root = Some(devices.join(":"));
+                options.root_devices = devices;
+            }
+        }
+        // btrfs auto-discovers sibling devices from any single member path,
+        // so only the first device is a valid mount(2) source; the rest are
+        // kept in root_devices purely so rootwait can wait on all of them.
+        Some("btrfs") => {
+            if let Some(root) = &options.root {
+                let devices: Vec<String> = root.split([',', ':']).map(str::to_string).collect();
+                if devices.len() > 1 {
+                    options.root = Some(devices[0].clone());
+                    options.root_devices = devices;
+                }
+            }
+        }
+        _ => {}
+    }
+    Ok(())
+}
+
+const NEW_ROOT: &str = "/newroot";
+const ROOT_POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+// For a multi-device bcachefs/btrfs root, `root` on its own may not name a
+// real path (a colon-joined bcachefs source) or may name only one member
+// (btrfs), so wait on every root_devices entry instead when it's populated.
+fn wait_for_root(options: &CmdlineOptions, root: &str) {
+    match options.rootfstype.as_deref() {
+        Some("nfs") | Some("9p") => return,
+        _ => (),
+    }
+    if let Some(delay) = options.rootdelay {
+        std::thread::sleep(delay);
+    }
+    if options.rootwait {
+        if options.root_devices.is_empty() {
+            while !Path::new(root).exists() {
+                std::thread::sleep(ROOT_POLL_INTERVAL);
+            }
+        } else {
+            for device in &options.root_devices {
+                while !Path::new(device).exists() {
+                    std::thread::sleep(ROOT_POLL_INTERVAL);
+                }
+            }
+        }
+    }
+}
+
+// The parser has already normalized the QEMU 9p, USB-gadget 9p, and NFS
+// cases into plain root/rootfstype/rootflags values, so they all flow
+// through this one mount + switch_root path with no further special-casing.
+pub fn mount_root(options: &CmdlineOptions) -> Result<()> {
+    let root = options.root.as_deref().ok_or("No root device specified!")?;
+    wait_for_root(options, root);
+    std::fs::create_dir_all(NEW_ROOT).map_err(|e| format!("Failed to create {NEW_ROOT}: {e}"))?;
+    nix::mount::mount(
+        Some(root),
+        NEW_ROOT,
+        options.rootfstype.as_deref(),
+        options.rootfsflags,
+        options.rootflags.as_deref(),
+    )
+    .map_err(|e| format!("Failed to mount {root} on {NEW_ROOT}: {e}"))?;
+    switch_root(NEW_ROOT, &options.init)
+}
+
+fn switch_root(new_root: &str, init: &CString) -> Result<()> {
+    let root_dev = std::fs::metadata("/")
+        .map_err(|e| format!("Failed to stat /: {e}"))?
+        .dev();
+    remove_tree("/", new_root, root_dev)?;
+    nix::unistd::chdir(new_root).map_err(|e| format!("Failed to chdir to {new_root}: {e}"))?;
+    nix::mount::mount(Some("."), "/", None::<&str>, MsFlags::MS_MOVE, None::<&str>)
+        .map_err(|e| format!("Failed to move {new_root} onto /: {e}"))?;
+    nix::unistd::chroot(".").map_err(|e| format!("Failed to chroot into {new_root}: {e}"))?;
+    nix::unistd::chdir("/").map_err(|e| format!("Failed to chdir to /: {e}"))?;
+    nix::unistd::execv(init, std::slice::from_ref(init))
+        .map_err(|e| format!("Failed to exec {init:?}: {e}"))?;
+    unreachable!("execv only returns on error")
+}
+
+// Skips skip (the new root's own mountpoint, left alone until it's moved
+// onto /) and anything not on root_dev (e.g. /proc, /sys, /dev), since
+// those are separate mounts whose mountpoints can't be rmdir'd anyway.
+fn remove_tree(dir: &str, skip: &str, root_dev: u64) -> Result<()> {
+    for entry in std::fs::read_dir(dir).map_err(|e| format!("Failed to read {dir}: {e}"))? {
+        let entry = entry.map_err(|e| format!("Failed to read entry in {dir}: {e}"))?;
+        let path = entry.path();
+        if path == Path::new(skip) {
+            continue;
+        }
+        let metadata = entry
+            .metadata()
+            .map_err(|e| format!("Failed to stat {}: {e}", path.display()))?;
+        if metadata.dev() != root_dev {
+            continue;
+        }
+        if metadata.is_dir() {
+            remove_tree(path.to_str().unwrap(), skip, root_dev)?;
+            std::fs::remove_dir(&path)
+                .map_err(|e| format!("Failed to remove {}: {e}", path.display()))?;
+        } else {
+            std::fs::remove_file(&path)
+                .map_err(|e| format!("Failed to remove {}: {e}", path.display()))?;
+        }
+    }
     Ok(())
 }
 
+pub fn unlock_root(options: &mut CmdlineOptions) -> Result<()> {
+    let luks = options
+        .luks
+        .as_ref()
+        .ok_or("Missing rd.luks.name option for encrypted root!")?;
+    let device = format!("/dev/disk/by-uuid/{}", luks.uuid);
+    let name = luks.name.clone();
+    let mut passphrase = read_passphrase(options)?;
+
+    let result = (|| -> Result<()> {
+        let mut child = std::process::Command::new("cryptsetup")
+            .args(["open", "--key-file", "-", &device, &name])
+            .stdin(std::process::Stdio::piped())
+            .spawn()
+            .map_err(|e| format!("Failed to spawn cryptsetup: {e}"))?;
+        // Wait on the child on every exit path below, even if writing the
+        // passphrase fails (e.g. cryptsetup exits early and closes its
+        // stdin), so it doesn't linger as a zombie.
+        let write_result = match child.stdin.take() {
+            Some(mut stdin) => stdin
+                .write_all(&passphrase)
+                .map_err(|e| format!("Failed to write passphrase to cryptsetup: {e}")),
+            None => Err("Failed to open cryptsetup stdin".to_string()),
+        };
+        let status = child
+            .wait()
+            .map_err(|e| format!("Failed to wait for cryptsetup: {e}"))?;
+        write_result?;
+        if !status.success() {
+            return Err(format!("cryptsetup open failed: {status}").into());
+        }
+        Ok(())
+    })();
+    zero_buffer(&mut passphrase);
+    result?;
+
+    options.root = Some(format!("/dev/mapper/{name}"));
+    Ok(())
+}
+
+fn read_passphrase(options: &mut CmdlineOptions) -> Result<Vec<u8>> {
+    if let Some(path) = &options.luks_key {
+        return std::fs::read(path)
+            .map_err(|e| format!("Failed to read key file {path}: {e}").into());
+    }
+    match options.cryptkey.take() {
+        None => prompt_passphrase(),
+        Some(mut baked) => {
+            let result = if baked == "none" {
+                prompt_passphrase()
+            } else {
+                Ok(baked.as_bytes().to_vec())
+            };
+            zero_buffer(unsafe { baked.as_bytes_mut() });
+            result
+        }
+    }
+}
+
+// No tty is attached to pid 1's own stdin this early in boot, so prompt on
+// /dev/console directly.
+fn prompt_passphrase() -> Result<Vec<u8>> {
+    let mut console = std::fs::OpenOptions::new()
+        .read(true)
+        .write(true)
+        .open("/dev/console")
+        .map_err(|e| format!("Failed to open /dev/console: {e}"))?;
+    console
+        .write_all(b"Enter passphrase for encrypted root: ")
+        .map_err(|e| format!("Failed to write prompt to /dev/console: {e}"))?;
+    let mut passphrase = String::new();
+    std::io::BufReader::new(console)
+        .read_line(&mut passphrase)
+        .map_err(|e| format!("Failed to read passphrase from /dev/console: {e}"))?;
+    let bytes = passphrase.trim_end_matches('\n').as_bytes().to_vec();
+    zero_buffer(unsafe { passphrase.as_bytes_mut() });
+    Ok(bytes)
+}
+
+// A plain `for` loop zeroing a buffer can be optimized away by the
+// compiler; the volatile write can't.
+fn zero_buffer(buf: &mut [u8]) {
+    for byte in buf.iter_mut() {
+        unsafe { std::ptr::write_volatile(byte, 0) };
+    }
+    std::sync::atomic::compiler_fence(std::sync::atomic::Ordering::SeqCst);
+}
+
+// Honors emergency/rd.shell by execing rescue_shell, and panic=<seconds> by
+// rebooting after a delay instead of leaving the kernel to panic with
+// "Attempted to kill init".
+pub fn rescue(options: &CmdlineOptions, error: impl std::fmt::Display) -> ! {
+    eprintln!("rsinit: {error}");
+    if options.emergency {
+        let _ = nix::unistd::execv(
+            &options.rescue_shell,
+            std::slice::from_ref(&options.rescue_shell),
+        );
+    }
+    if let Some(delay) = options.panic_timeout {
+        std::thread::sleep(delay);
+        let _ = nix::sys::reboot::reboot(nix::sys::reboot::RebootMode::RB_AUTOBOOT);
+    }
+    std::process::exit(1);
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -234,4 +636,163 @@ mod tests {
         assert!(options.nfsroot.is_none());
         assert_eq!(options.init, CString::from(c"/bin/sh"));
     }
+
+    #[test]
+    fn test_rootwait_rootdelay() {
+        let cmdline = String::from("root=/dev/mmcblk0p1 rootwait rootdelay=5 rw\n");
+        let mut options = CmdlineOptions {
+            ..Default::default()
+        };
+
+        parse_cmdline(cmdline, &mut options).expect("failed");
+        assert!(options.rootwait);
+        assert_eq!(options.rootdelay, Some(Duration::from_secs(5)));
+    }
+
+    #[test]
+    fn test_luks_options() {
+        let cmdline = String::from(
+            "root=/dev/mapper/cryptroot rd.luks.name=8b0b3b6e-1111-2222-3333-abcdefabcdef=cryptroot rd.luks.key=/keys/root.key cryptkey=none\n",
+        );
+        let mut options = CmdlineOptions {
+            ..Default::default()
+        };
+
+        parse_cmdline(cmdline, &mut options).expect("failed");
+        let luks = options.luks.as_ref().expect("luks config missing");
+        assert_eq!(luks.uuid, "8b0b3b6e-1111-2222-3333-abcdefabcdef");
+        assert_eq!(luks.name, "cryptroot");
+        assert_eq!(options.luks_key.as_deref(), Some("/keys/root.key"));
+        assert_eq!(options.cryptkey.as_deref(), Some("none"));
+    }
+
+    #[test]
+    fn test_has_var_and_lookup() {
+        let cmdline = String::from("root=/dev/mmcblk0p1 rootwait console=ttyS0 cryptkey=secret\n");
+        let mut options = CmdlineOptions {
+            ..Default::default()
+        };
+
+        parse_cmdline(cmdline, &mut options).expect("failed");
+        assert!(options.has_var("rootwait"));
+        assert!(!options.has_var("rootdelay"));
+        assert_eq!(options.lookup("console").as_deref(), Some("ttyS0"));
+        assert_eq!(options.lookup("rootwait"), None);
+        assert_eq!(options.lookup("missing"), None);
+        // cryptkey is parsed directly into options.cryptkey and deliberately
+        // excluded from vars; see the field doc comment above.
+        assert!(!options.has_var("cryptkey"));
+    }
+
+    #[test]
+    fn test_bcachefs_multi_device() {
+        let cmdline =
+            String::from("root=/dev/sda:/dev/sdb rootfstype=bcachefs rootflags=degraded rw\n");
+        let mut options = CmdlineOptions {
+            ..Default::default()
+        };
+
+        parse_cmdline(cmdline, &mut options).expect("failed");
+        assert_eq!(options.root.as_deref(), Some("/dev/sda:/dev/sdb"));
+        assert_eq!(
+            options.root_devices,
+            vec!["/dev/sda".to_string(), "/dev/sdb".to_string()]
+        );
+        assert_eq!(options.rootflags.as_deref(), Some("degraded"));
+    }
+
+    #[test]
+    fn test_bcachefs_comma_separated() {
+        let cmdline = String::from("root=/dev/sda,/dev/sdb rootfstype=bcachefs\n");
+        let mut options = CmdlineOptions {
+            ..Default::default()
+        };
+
+        parse_cmdline(cmdline, &mut options).expect("failed");
+        assert_eq!(options.root.as_deref(), Some("/dev/sda:/dev/sdb"));
+        assert_eq!(
+            options.root_devices,
+            vec!["/dev/sda".to_string(), "/dev/sdb".to_string()]
+        );
+    }
+
+    #[test]
+    fn test_btrfs_multi_device() {
+        let cmdline = String::from("root=/dev/sda:/dev/sdb rootfstype=btrfs rw\n");
+        let mut options = CmdlineOptions {
+            ..Default::default()
+        };
+
+        parse_cmdline(cmdline, &mut options).expect("failed");
+        assert_eq!(options.root.as_deref(), Some("/dev/sda"));
+        assert_eq!(
+            options.root_devices,
+            vec!["/dev/sda".to_string(), "/dev/sdb".to_string()]
+        );
+    }
+
+    #[test]
+    fn test_btrfs_single_device() {
+        let cmdline = String::from("root=/dev/sda1 rootfstype=btrfs rw\n");
+        let mut options = CmdlineOptions {
+            ..Default::default()
+        };
+
+        parse_cmdline(cmdline, &mut options).expect("failed");
+        assert_eq!(options.root.as_deref(), Some("/dev/sda1"));
+        assert!(options.root_devices.is_empty());
+    }
+
+    #[test]
+    fn test_emergency_and_panic() {
+        let cmdline =
+            String::from("root=/dev/mmcblk0p1 rd.shell rescue_shell=/bin/busybox panic=30\n");
+        let mut options = CmdlineOptions {
+            ..Default::default()
+        };
+
+        parse_cmdline(cmdline, &mut options).expect("failed");
+        assert!(options.emergency);
+        assert_eq!(options.rescue_shell, CString::new("/bin/busybox").unwrap());
+        assert_eq!(options.panic_timeout, Some(Duration::from_secs(30)));
+    }
+
+    #[test]
+    fn test_nfs_server_from_ip() {
+        let cmdline = String::from(
+            "root=/dev/nfs nfsroot=/path/to/nfsroot ip=10.0.2.15:10.0.2.2::255.255.255.0::eth0:off rootwait ro\n",
+        );
+        let mut options = CmdlineOptions {
+            ..Default::default()
+        };
+
+        parse_cmdline(cmdline, &mut options).expect("failed");
+        assert_eq!(options.root.as_deref(), Some("10.0.2.2:/path/to/nfsroot"));
+        assert_eq!(options.rootflags.as_deref(), Some("nolock,addr=10.0.2.2"));
+        let ip = options.ip.as_ref().expect("ip config missing");
+        assert_eq!(ip.client_ip.as_deref(), Some("10.0.2.15"));
+        assert_eq!(ip.server_ip.as_deref(), Some("10.0.2.2"));
+        assert_eq!(ip.gw_ip, None);
+        assert_eq!(ip.netmask.as_deref(), Some("255.255.255.0"));
+        assert_eq!(ip.device.as_deref(), Some("eth0"));
+        assert_eq!(ip.autoconf, Autoconf::Off);
+    }
+
+    #[test]
+    fn test_ip_dhcp_shorthand() {
+        let cmdline = String::from("root=/dev/mmcblk0p1 ip=dhcp\n");
+        let mut options = CmdlineOptions {
+            ..Default::default()
+        };
+
+        parse_cmdline(cmdline, &mut options).expect("failed");
+        let ip = options.ip.as_ref().expect("ip config missing");
+        assert_eq!(
+            *ip,
+            IpConfig {
+                autoconf: Autoconf::Dhcp,
+                ..Default::default()
+            }
+        );
+    }
 }